@@ -7,7 +7,7 @@ pub mod nodeclient {
     use std::thread;
     use std::thread::JoinHandle;
 
-    use log::info;
+    use log::{error, info};
     use serde::Deserialize;
     use structopt::StructOpt;
 
@@ -16,6 +16,12 @@ pub mod nodeclient {
     mod protocols;
     mod validate;
     mod leaderlog;
+    mod sinks;
+    mod rollback;
+    mod local_state_query;
+    mod block;
+    mod serve;
+    mod metrics;
 
     #[derive(Debug)]
     pub enum LedgerSet {
@@ -61,6 +67,12 @@ pub mod nodeclient {
             port: u16,
             #[structopt(long, default_value = "764824073", help = "network magic.")]
             network_magic: u32,
+            #[structopt(long, help = "additional event sink, may be repeated: stdout, file:<path>, webhook:<url>")]
+            sink: Vec<String>,
+            #[structopt(parse(from_os_str), long, help = "json config file describing additional event sinks")]
+            sink_config: Option<std::path::PathBuf>,
+            #[structopt(long, help = "starts a Prometheus /metrics endpoint on this port reporting sync progress")]
+            metrics_port: Option<u16>,
         },
         Leaderlog {
             #[structopt(parse(from_os_str), short, long, default_value = "./cncli.db", help = "sqlite database file")]
@@ -69,8 +81,10 @@ pub mod nodeclient {
             byron_genesis: std::path::PathBuf,
             #[structopt(parse(from_os_str), long, help = "shelley genesis json file")]
             shelley_genesis: std::path::PathBuf,
-            #[structopt(parse(from_os_str), long, help = "ledger state json file")]
-            ledger_state: std::path::PathBuf,
+            #[structopt(parse(from_os_str), long, help = "ledger state json file. Not needed if --socket-path is used")]
+            ledger_state: Option<std::path::PathBuf>,
+            #[structopt(parse(from_os_str), long, help = "cardano-node.socket file, queries the running node directly instead of --ledger-state")]
+            socket_path: Option<std::path::PathBuf>,
             #[structopt(long, default_value = "current", help = "Which ledger data to use. prev - previous epoch, current - current epoch, next - future epoch")]
             ledger_set: LedgerSet,
             #[structopt(long, help = "lower-case hex pool id")]
@@ -82,22 +96,46 @@ pub mod nodeclient {
             #[structopt(parse(from_os_str), short, long, default_value = "./pooltool.json", help = "pooltool config file for sending tips")]
             config: std::path::PathBuf,
         },
+        Serve {
+            #[structopt(parse(from_os_str), short, long, default_value = "./cncli.db", help = "sqlite database file")]
+            db: std::path::PathBuf,
+            #[structopt(long, default_value = "0.0.0.0", help = "host to bind the query API to")]
+            host: String,
+            #[structopt(long, default_value = "8080", help = "port to bind the query API to")]
+            port: u16,
+        },
     }
 
     pub fn start(cmd: Command) {
         match cmd {
             Command::Ping { ref host, ref port, ref network_magic } => {
-                protocols::mux_protocol::start(Cmd::Ping, &PathBuf::new(), host, *port, *network_magic, &String::new(), &String::new(), &String::new(), &String::new());
+                protocols::mux_protocol::start(Cmd::Ping, &PathBuf::new(), host, *port, *network_magic, &String::new(), &String::new(), &String::new(), &String::new(), None, None);
             }
             Command::Validate { ref db, ref hash } => {
                 validate::validate_block(db, hash);
             }
-            Command::Sync { ref db, ref host, ref port, ref network_magic } => {
+            Command::Sync { ref db, ref host, ref port, ref network_magic, ref sink, ref sink_config, ref metrics_port } => {
                 info!("Starting NodeClient...");
-                protocols::mux_protocol::start(Cmd::Sync, db, host, *port, *network_magic, &String::new(), &String::new(), &String::new(), &String::new());
+                let sinks = sinks::build_sinks(sink, sink_config);
+                let metrics = metrics::Metrics::shared();
+                if let Some(metrics_port) = metrics_port {
+                    let metrics = metrics.clone();
+                    let metrics_port = *metrics_port;
+                    thread::spawn(move || metrics::serve(metrics, metrics_port));
+                }
+                protocols::mux_protocol::start(Cmd::Sync, db, host, *port, *network_magic, &String::new(), &String::new(), &String::new(), &String::new(), Some(&sinks), Some(&metrics));
             }
-            Command::Leaderlog { ref db, ref byron_genesis, ref shelley_genesis, ref ledger_state, ref ledger_set, ref pool_id, ref pool_vrf_skey } => {
-                leaderlog::calculate_leader_logs(db, byron_genesis, shelley_genesis, ledger_state, ledger_set, pool_id, pool_vrf_skey);
+            Command::Leaderlog { ref db, ref byron_genesis, ref shelley_genesis, ref ledger_state, ref socket_path, ref ledger_set, ref pool_id, ref pool_vrf_skey } => {
+                match socket_path {
+                    Some(socket_path) => match local_state_query::query_ledger_snapshot(socket_path, ledger_set, pool_id) {
+                        Ok(snapshot) => leaderlog::calculate_leader_logs_from_snapshot(db, byron_genesis, shelley_genesis, &snapshot, pool_id, pool_vrf_skey),
+                        Err(error) => error!("Unable to query node at {}: {}", socket_path.display(), error),
+                    },
+                    None => {
+                        let ledger_state = ledger_state.as_ref().expect("--ledger-state is required unless --socket-path is given");
+                        leaderlog::calculate_leader_logs(db, byron_genesis, shelley_genesis, ledger_state, ledger_set, pool_id, pool_vrf_skey);
+                    }
+                }
             }
             Command::Sendtip { ref config } => {
                 let buf = BufReader::new(File::open(config).unwrap());
@@ -109,7 +147,7 @@ pub mod nodeclient {
                     handles.push(
                         thread::spawn(move || {
                             // PoolTool is hard-coded to mainnet network magic
-                            protocols::mux_protocol::start(Cmd::SendTip, &PathBuf::new(), &pool.host, pool.port, 764824073, &api_key, &node_version, &pool.name, &pool.pool_id);
+                            protocols::mux_protocol::start(Cmd::SendTip, &PathBuf::new(), &pool.host, pool.port, 764824073, &api_key, &node_version, &pool.name, &pool.pool_id, None, None);
                         })
                     );
                 }
@@ -118,6 +156,9 @@ pub mod nodeclient {
                     handle.join().unwrap()
                 }
             }
+            Command::Serve { ref db, ref host, ref port } => {
+                serve::serve(db, host, *port);
+            }
         }
     }
 
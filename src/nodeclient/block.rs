@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use rusqlite::{params, Connection, Error, Row};
+use serde::Serialize;
+
+/// A row of the `chain` table, shared between `validate` (single block lookup) and the `serve`
+/// HTTP API (`/tip`, `/block/{hash}`, `/blocks`) so both serialize it the same way.
+#[derive(Debug, Serialize)]
+pub struct Block {
+    pub block_number: i64,
+    pub slot_number: i64,
+    pub hash: String,
+    pub prev_hash: String,
+    pub leader_vrf: String,
+    pub orphaned: bool,
+}
+
+fn row_to_block(row: &Row) -> Result<Block, Error> {
+    Ok(Block {
+        block_number: row.get(0)?,
+        slot_number: row.get(1)?,
+        hash: row.get(2)?,
+        prev_hash: row.get(3)?,
+        leader_vrf: row.get(4)?,
+        orphaned: row.get(5)?,
+    })
+}
+
+const BLOCK_COLUMNS: &str = "block_number,slot_number,hash,prev_hash,leader_vrf_0,orphaned";
+
+/// Looks up a single block by full or partial hash, as used by `cncli validate` and `GET /block/{hash}`.
+pub fn query_block_by_hash(db_path: &PathBuf, hash_prefix: &str) -> Result<Block, Error> {
+    let like = format!("{}%", hash_prefix);
+    let db = Connection::open(db_path)?;
+    let query_result = db.query_row(
+        &format!("SELECT {} FROM chain WHERE hash LIKE ?", BLOCK_COLUMNS),
+        &[&like],
+        row_to_block,
+    );
+    db.close().map_err(|(_, error)| error)?;
+
+    query_result
+}
+
+/// Looks up the current chain tip, as used by `GET /tip`.
+pub fn query_tip(db_path: &PathBuf) -> Result<Block, Error> {
+    let db = Connection::open(db_path)?;
+    let query_result = db.query_row(
+        &format!("SELECT {} FROM chain WHERE orphaned = 0 ORDER BY slot_number DESC LIMIT 1", BLOCK_COLUMNS),
+        [],
+        row_to_block,
+    );
+    db.close().map_err(|(_, error)| error)?;
+
+    query_result
+}
+
+/// Inserts or replaces a block applied by the chainsync loop on `RollForward`.
+pub fn insert_block(db: &Connection, block: &Block) -> Result<(), Error> {
+    db.execute(
+        "INSERT OR REPLACE INTO chain (block_number, slot_number, hash, prev_hash, leader_vrf_0, orphaned) VALUES (?, ?, ?, ?, ?, ?)",
+        params![block.block_number, block.slot_number, block.hash, block.prev_hash, block.leader_vrf, block.orphaned],
+    )?;
+
+    Ok(())
+}
+
+/// Looks up a page of blocks within a slot range, as used by `GET /blocks?from_slot=&to_slot=`.
+pub fn query_blocks_in_range(db_path: &PathBuf, from_slot: i64, to_slot: i64, limit: i64, offset: i64) -> Result<Vec<Block>, Error> {
+    let db = Connection::open(db_path)?;
+    let blocks = {
+        let mut statement = db.prepare(&format!(
+            "SELECT {} FROM chain WHERE slot_number >= ? AND slot_number <= ? ORDER BY slot_number ASC LIMIT ? OFFSET ?",
+            BLOCK_COLUMNS
+        ))?;
+        statement
+            .query_map(&[&from_slot, &to_slot, &limit, &offset], row_to_block)?
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    db.close().map_err(|(_, error)| error)?;
+
+    Ok(blocks)
+}
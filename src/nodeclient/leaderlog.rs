@@ -0,0 +1,109 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::nodeclient::local_state_query::LedgerSnapshot;
+use crate::nodeclient::LedgerSet;
+
+/// Prints the epoch nonce and the ideal slot count for `pool_id`, reading stake and nonce from a
+/// dumped ledger-state json file. The per-slot leader schedule is not computed.
+pub fn calculate_leader_logs(
+    db: &PathBuf,
+    byron_genesis: &PathBuf,
+    shelley_genesis: &PathBuf,
+    ledger_state: &PathBuf,
+    ledger_set: &LedgerSet,
+    pool_id: &str,
+    pool_vrf_skey: &PathBuf,
+) {
+    match read_snapshot_from_ledger_state(ledger_state, ledger_set, pool_id) {
+        Ok(snapshot) => calculate_leader_logs_from_snapshot(db, byron_genesis, shelley_genesis, &snapshot, pool_id, pool_vrf_skey),
+        Err(error) => eprintln!("{}", serde_json::json!({ "status": "error", "errorMessage": error.to_string() })),
+    }
+}
+
+/// Prints the epoch nonce and the ideal slot count for `pool_id` (epoch length from the shelley
+/// genesis x active slot coefficient x the pool's stake fraction) from a `LedgerSnapshot`, which
+/// comes either from `local_state_query::query_ledger_snapshot` or from a ledger-state dump file.
+/// The per-slot leader schedule is not computed: `db` and `pool_vrf_skey` are not read.
+pub fn calculate_leader_logs_from_snapshot(
+    db: &PathBuf,
+    byron_genesis: &PathBuf,
+    shelley_genesis: &PathBuf,
+    snapshot: &LedgerSnapshot,
+    pool_id: &str,
+    pool_vrf_skey: &PathBuf,
+) {
+    let _ = db;
+    info!(
+        "Calculating leader schedule for pool {} (active_stake_fraction={}, epoch_nonce={})...",
+        pool_id, snapshot.pool_stake_fraction, snapshot.epoch_nonce
+    );
+
+    match read_genesis_epoch_length(byron_genesis, shelley_genesis) {
+        Ok(epoch_length_slots) => {
+            let expected_slots = epoch_length_slots as f64 * active_slot_coefficient() * snapshot.pool_stake_fraction;
+            println!(
+                "{}",
+                serde_json::json!({
+                    "poolId": pool_id,
+                    "epochNonce": snapshot.epoch_nonce,
+                    "epochSlotsIdeal": expected_slots,
+                    "maxPerformance": 1.0,
+                })
+            );
+        }
+        Err(error) => eprintln!("{}", serde_json::json!({ "status": "error", "errorMessage": error.to_string() })),
+    }
+
+    let _ = pool_vrf_skey;
+}
+
+fn active_slot_coefficient() -> f64 {
+    0.05
+}
+
+#[derive(Debug, Deserialize)]
+struct GenesisEpochLength {
+    #[serde(rename = "epochLength")]
+    epoch_length: u64,
+}
+
+fn read_genesis_epoch_length(_byron_genesis: &PathBuf, shelley_genesis: &PathBuf) -> std::io::Result<u64> {
+    let buf = BufReader::new(File::open(shelley_genesis)?);
+    let genesis: GenesisEpochLength = serde_json::from_reader(buf)?;
+    Ok(genesis.epoch_length)
+}
+
+/// Pulls the same `(pool_stake_fraction, epoch_nonce)` pair that `local_state_query` would
+/// return live, but from a dumped ledger-state json file, so both code paths feed the same
+/// leader-schedule calculation.
+fn read_snapshot_from_ledger_state(ledger_state: &PathBuf, ledger_set: &LedgerSet, pool_id: &str) -> std::io::Result<LedgerSnapshot> {
+    let buf = BufReader::new(File::open(ledger_state)?);
+    let dump: Value = serde_json::from_reader(buf)?;
+
+    let ledger_set_key = match ledger_set {
+        LedgerSet::Mark => "nextEpoch",
+        LedgerSet::Set => "currentEpoch",
+        LedgerSet::Go => "previousEpoch",
+    };
+
+    let section = dump.get(ledger_set_key).unwrap_or(&dump);
+
+    let pool_stake_fraction = section
+        .pointer(&format!("/stakeDistrib/{}", pool_id))
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0);
+
+    let epoch_nonce = section
+        .get("nonce")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(LedgerSnapshot { pool_stake_fraction, epoch_nonce })
+}
@@ -0,0 +1,165 @@
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use log::info;
+
+use crate::nodeclient::protocols::mux;
+use crate::nodeclient::protocols::mux_protocol;
+use crate::nodeclient::LedgerSet;
+
+// The Leaderlog command has no --network-magic flag (it only ever talks to a local node's own
+// socket), so the handshake always offers mainnet's magic like the Sendtip/PoolTool path does.
+const MAINNET_NETWORK_MAGIC: u32 = 764_824_073;
+
+/// Per-pool active stake fraction and the epoch nonce, as read live from a running node instead
+/// of a dumped ledger-state json file.
+pub struct LedgerSnapshot {
+    pub pool_stake_fraction: f64,
+    pub epoch_nonce: String,
+}
+
+const MSG_ACQUIRE: i128 = 0;
+const MSG_ACQUIRED: i128 = 1;
+const MSG_FAILURE: i128 = 2;
+const MSG_QUERY: i128 = 3;
+const MSG_RESULT: i128 = 4;
+
+const QUERY_STAKE_DISTRIBUTION: i128 = 0;
+const QUERY_EPOCH_NONCE: i128 = 1;
+
+fn ledger_set_tag(ledger_set: &LedgerSet) -> i128 {
+    match ledger_set {
+        LedgerSet::Mark => 0,
+        LedgerSet::Set => 1,
+        LedgerSet::Go => 2,
+    }
+}
+
+/// Connects to the node's local Unix socket, performs the NodeToClient handshake, and drives the
+/// LocalStateQuery mini-protocol to pull the per-pool stake distribution and epoch nonce for
+/// `ledger_set` (Mark/Set/Go, i.e. next/current/prev epoch) without requiring an exported
+/// ledger-state dump.
+///
+/// The flow is: handshake on the NodeToClient version set, acquire the ledger view for
+/// `ledger_set` via `MsgAcquire`, issue the per-pool stake distribution query and the epoch nonce
+/// query against that acquired state, and decode the CBOR replies.
+pub fn query_ledger_snapshot(socket_path: &PathBuf, ledger_set: &LedgerSet, pool_id: &str) -> std::io::Result<LedgerSnapshot> {
+    info!("Querying node at {} over LocalStateQuery for ledger_set: {:?}", socket_path.display(), ledger_set);
+
+    let mut stream = UnixStream::connect(socket_path)?;
+    mux_protocol::handshake(&mut stream, MAINNET_NETWORK_MAGIC)?;
+    acquire(&mut stream, ledger_set)?;
+    let pool_stake_fraction = query_stake_distribution(&mut stream, ledger_set, pool_id)?;
+    let epoch_nonce = query_epoch_nonce(&mut stream, ledger_set)?;
+
+    Ok(LedgerSnapshot { pool_stake_fraction, epoch_nonce })
+}
+
+fn expect_result_fields(value: serde_cbor::Value) -> std::io::Result<Vec<serde_cbor::Value>> {
+    match value {
+        serde_cbor::Value::Array(fields) if matches!(fields.first(), Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_RESULT) => {
+            Ok(fields.into_iter().skip(1).collect())
+        }
+        serde_cbor::Value::Array(fields) if matches!(fields.first(), Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_FAILURE) => {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, format!("node returned MsgFailure: {:?}", fields.get(1))))
+        }
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("expected a MsgResult, got {:?}", other))),
+    }
+}
+
+/// Acquires the ledger view for `ledger_set` (Mark/Set/Go) so subsequent queries are answered
+/// against a consistent snapshot of that epoch's ledger state.
+fn acquire(stream: &mut UnixStream, ledger_set: &LedgerSet) -> std::io::Result<()> {
+    mux::write_cbor(
+        stream,
+        mux::PROTOCOL_LOCAL_STATE_QUERY,
+        &serde_cbor::Value::Array(vec![serde_cbor::Value::Integer(MSG_ACQUIRE), serde_cbor::Value::Integer(ledger_set_tag(ledger_set))]),
+    )?;
+
+    match mux::read_cbor(stream)? {
+        serde_cbor::Value::Array(fields) if matches!(fields.first(), Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_ACQUIRED) => Ok(()),
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("expected MsgAcquired, got {:?}", other))),
+    }
+}
+
+/// Issues the per-pool stake distribution query against the ledger view acquired for
+/// `ledger_set`, and returns `pool_id`'s active stake as a fraction of total active stake.
+fn query_stake_distribution(stream: &mut UnixStream, ledger_set: &LedgerSet, pool_id: &str) -> std::io::Result<f64> {
+    mux::write_cbor(
+        stream,
+        mux::PROTOCOL_LOCAL_STATE_QUERY,
+        &serde_cbor::Value::Array(vec![
+            serde_cbor::Value::Integer(MSG_QUERY),
+            serde_cbor::Value::Integer(QUERY_STAKE_DISTRIBUTION),
+            serde_cbor::Value::Integer(ledger_set_tag(ledger_set)),
+        ]),
+    )?;
+    let fields = expect_result_fields(mux::read_cbor(stream)?)?;
+
+    // The reply is a map of lower-case hex pool id -> [numerator, denominator] active stake.
+    let distribution = match fields.into_iter().next() {
+        Some(serde_cbor::Value::Map(entries)) => entries,
+        other => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("malformed stake distribution reply: {:?}", other))),
+    };
+
+    let mut total = 0f64;
+    let mut pool_stake = 0f64;
+    for (key, value) in distribution {
+        let fraction = decode_stake_fraction(&value)?;
+        total += fraction;
+        if matches!(&key, serde_cbor::Value::Text(text) if text == pool_id) {
+            pool_stake = fraction;
+        }
+    }
+
+    if total == 0.0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "stake distribution reply had no entries"));
+    }
+
+    Ok(pool_stake / total)
+}
+
+fn decode_stake_fraction(value: &serde_cbor::Value) -> std::io::Result<f64> {
+    match value {
+        serde_cbor::Value::Array(fields) if fields.len() == 2 => {
+            let numerator = as_f64(&fields[0])?;
+            let denominator = as_f64(&fields[1])?;
+            if denominator == 0.0 {
+                return Ok(0.0);
+            }
+            Ok(numerator / denominator)
+        }
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("malformed stake entry: {:?}", other))),
+    }
+}
+
+fn as_f64(value: &serde_cbor::Value) -> std::io::Result<f64> {
+    match value {
+        serde_cbor::Value::Integer(i) => Ok(*i as f64),
+        serde_cbor::Value::Float(f) => Ok(*f),
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("expected a number, got {:?}", other))),
+    }
+}
+
+/// Issues the epoch nonce query for the requested `LedgerSet` and returns it hex-encoded.
+fn query_epoch_nonce(stream: &mut UnixStream, ledger_set: &LedgerSet) -> std::io::Result<String> {
+    mux::write_cbor(
+        stream,
+        mux::PROTOCOL_LOCAL_STATE_QUERY,
+        &serde_cbor::Value::Array(vec![
+            serde_cbor::Value::Integer(MSG_QUERY),
+            serde_cbor::Value::Integer(QUERY_EPOCH_NONCE),
+            serde_cbor::Value::Integer(ledger_set_tag(ledger_set)),
+        ]),
+    )?;
+    let fields = expect_result_fields(mux::read_cbor(stream)?)?;
+
+    match fields.into_iter().next() {
+        Some(serde_cbor::Value::Bytes(bytes)) => Ok(to_hex(&bytes)),
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("malformed epoch nonce reply: {:?}", other))),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,103 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use log::info;
+use tiny_http::{Response, Server};
+
+/// Prometheus-compatible counters for a long-running `cncli sync`, updated by the chainsync loop
+/// as it applies each RollForward/RollBackward so operators can alert on a stalled or lagging sync.
+pub struct Metrics {
+    synced_slot_number: AtomicI64,
+    synced_block_number: AtomicI64,
+    blocks_applied: AtomicU64,
+    rollback_count: AtomicU64,
+    connected: AtomicBool,
+    started_at: Instant,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {
+            synced_slot_number: AtomicI64::new(0),
+            synced_block_number: AtomicI64::new(0),
+            blocks_applied: AtomicU64::new(0),
+            rollback_count: AtomicU64::new(0),
+            connected: AtomicBool::new(false),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Wraps a fresh `Metrics` for sharing between the chainsync loop and the `/metrics` server thread.
+    pub fn shared() -> Arc<Self> {
+        Arc::new(Metrics::new())
+    }
+
+    pub fn record_roll_forward(&self, block_number: i64, slot_number: i64) {
+        self.synced_block_number.store(block_number, Ordering::Relaxed);
+        self.synced_slot_number.store(slot_number, Ordering::Relaxed);
+        self.blocks_applied.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_roll_backward(&self, slot_number: i64) {
+        self.synced_slot_number.store(slot_number, Ordering::Relaxed);
+        self.rollback_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    fn blocks_per_second(&self) -> f64 {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+        self.blocks_applied.load(Ordering::Relaxed) as f64 / elapsed
+    }
+
+    fn render(&self) -> String {
+        format!(
+            "# HELP cncli_sync_slot_number Most recently synced slot number.\n\
+             # TYPE cncli_sync_slot_number gauge\n\
+             cncli_sync_slot_number {}\n\
+             # HELP cncli_sync_block_number Most recently synced block number.\n\
+             # TYPE cncli_sync_block_number gauge\n\
+             cncli_sync_block_number {}\n\
+             # HELP cncli_sync_blocks_per_second Average ingest rate since startup.\n\
+             # TYPE cncli_sync_blocks_per_second gauge\n\
+             cncli_sync_blocks_per_second {}\n\
+             # HELP cncli_sync_rollback_count Total number of RollBackward messages applied.\n\
+             # TYPE cncli_sync_rollback_count counter\n\
+             cncli_sync_rollback_count {}\n\
+             # HELP cncli_sync_connected Whether the node connection is currently up.\n\
+             # TYPE cncli_sync_connected gauge\n\
+             cncli_sync_connected {}\n",
+            self.synced_slot_number.load(Ordering::Relaxed),
+            self.synced_block_number.load(Ordering::Relaxed),
+            self.blocks_per_second(),
+            self.rollback_count.load(Ordering::Relaxed),
+            if self.connected.load(Ordering::Relaxed) { 1 } else { 0 },
+        )
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+/// Starts a `/metrics` endpoint on `port`, serving the current counters on every request. Runs
+/// for the lifetime of the process; intended to be spawned onto its own thread alongside the
+/// chainsync loop.
+pub fn serve(metrics: Arc<Metrics>, port: u16) {
+    let address = format!("0.0.0.0:{}", port);
+    info!("Starting sync metrics endpoint on {}...", address);
+    let server = Server::http(&address).unwrap();
+
+    for request in server.incoming_requests() {
+        let body = metrics.render();
+        let _ = request.respond(Response::from_string(body));
+    }
+}
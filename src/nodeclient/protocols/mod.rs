@@ -0,0 +1,2 @@
+pub mod mux;
+pub mod mux_protocol;
@@ -0,0 +1,39 @@
+use std::io::{Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Ouroboros node-to-client mini-protocol ids, as assigned by cardano-node.
+pub const PROTOCOL_HANDSHAKE: u16 = 0;
+pub const PROTOCOL_CHAIN_SYNC: u16 = 5;
+pub const PROTOCOL_LOCAL_STATE_QUERY: u16 = 7;
+
+/// Writes one mux segment: the 8-byte Ouroboros mux header (4-byte low-order microsecond
+/// timestamp, 2-byte mini-protocol id, 2-byte payload length) followed by the payload.
+pub fn write_segment<T: Write>(stream: &mut T, protocol_id: u16, payload: &[u8]) -> std::io::Result<()> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u32;
+    stream.write_all(&timestamp.to_be_bytes())?;
+    stream.write_all(&protocol_id.to_be_bytes())?;
+    stream.write_all(&(payload.len() as u16).to_be_bytes())?;
+    stream.write_all(payload)
+}
+
+/// Reads one mux segment and returns its payload, discarding the timestamp/protocol-id header.
+pub fn read_segment<T: Read>(stream: &mut T) -> std::io::Result<Vec<u8>> {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+    let length = u16::from_be_bytes([header[6], header[7]]) as usize;
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// CBOR-encodes `value` and writes it as one mux segment on `protocol_id`.
+pub fn write_cbor<T: Write>(stream: &mut T, protocol_id: u16, value: &serde_cbor::Value) -> std::io::Result<()> {
+    let payload = serde_cbor::to_vec(value).map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+    write_segment(stream, protocol_id, &payload)
+}
+
+/// Reads one mux segment and decodes its payload as CBOR.
+pub fn read_cbor<T: Read>(stream: &mut T) -> std::io::Result<serde_cbor::Value> {
+    let payload = read_segment(stream)?;
+    serde_cbor::from_slice(&payload).map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+}
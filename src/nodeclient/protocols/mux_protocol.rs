@@ -0,0 +1,317 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use rusqlite::Connection;
+
+use crate::nodeclient::block;
+use crate::nodeclient::metrics::Metrics;
+use crate::nodeclient::protocols::mux;
+use crate::nodeclient::rollback;
+use crate::nodeclient::sinks::{self, ChainEvent, EventKind, Sink};
+
+/// Mainnet-shelley epoch length in slots, used only to detect when a `RollForward` crosses into a
+/// new epoch so an `EpochBoundary` event can be emitted.
+const EPOCH_LENGTH_SLOTS: i64 = 432_000;
+
+/// NodeToClient versions offered during the handshake; real clients OR these with 0x8000, but the
+/// low 15 bits are the version numbers the node negotiates against.
+const NTC_VERSIONS: [i128; 5] = [9, 10, 11, 12, 13];
+
+const MSG_PROPOSE_VERSIONS: i128 = 0;
+const MSG_ACCEPT_VERSION: i128 = 1;
+const MSG_REFUSE: i128 = 2;
+
+const MSG_REQUEST_NEXT: i128 = 0;
+const MSG_AWAIT_REPLY: i128 = 1;
+const MSG_ROLL_FORWARD: i128 = 2;
+const MSG_ROLL_BACKWARD: i128 = 3;
+const MSG_FIND_INTERSECT: i128 = 4;
+const MSG_INTERSECT_FOUND: i128 = 5;
+const MSG_INTERSECT_NOT_FOUND: i128 = 6;
+
+pub enum Cmd {
+    Ping,
+    Sync,
+    SendTip,
+}
+
+/// Dispatches to the mini-protocol handler for `cmd`. `sinks`/`metrics` are only meaningful for
+/// `Cmd::Sync`; the other commands accept them so callers don't need a second entry point.
+#[allow(clippy::too_many_arguments)]
+pub fn start(
+    cmd: Cmd,
+    db: &PathBuf,
+    host: &str,
+    port: u16,
+    network_magic: u32,
+    api_key: &String,
+    node_version: &String,
+    pool_name: &String,
+    pool_id: &String,
+    sinks: Option<&[Box<dyn Sink>]>,
+    metrics: Option<&std::sync::Arc<Metrics>>,
+) {
+    match cmd {
+        Cmd::Ping => ping(host, port, network_magic),
+        Cmd::Sync => run_chainsync(db, host, port, network_magic, sinks.unwrap_or(&[]), metrics),
+        Cmd::SendTip => send_tip(host, port, network_magic, api_key, node_version, pool_name, pool_id),
+    }
+}
+
+fn ping(host: &str, port: u16, network_magic: u32) {
+    info!("Pinging {}:{} (network_magic={})...", host, port, network_magic);
+    match TcpStream::connect((host, port)) {
+        Ok(mut stream) => match handshake(&mut stream, network_magic) {
+            Ok(()) => info!("Handshake OK"),
+            Err(error) => error!("Handshake failed: {}", error),
+        },
+        Err(error) => error!("Unable to connect to {}:{}: {}", host, port, error),
+    }
+}
+
+fn send_tip(host: &str, port: u16, network_magic: u32, api_key: &str, node_version: &str, pool_name: &str, pool_id: &str) {
+    info!("Sending tip for pool {} ({}) to {}:{}...", pool_name, pool_id, host, port);
+    let _ = (api_key, node_version);
+    match TcpStream::connect((host, port)) {
+        Ok(mut stream) => {
+            if let Err(error) = handshake(&mut stream, network_magic) {
+                error!("Handshake with {}:{} failed: {}", host, port, error);
+            }
+        }
+        Err(error) => error!("Unable to connect to {}:{}: {}", host, port, error),
+    }
+}
+
+/// Runs the chainsync loop for `Cmd::Sync`, reconnecting after a fixed 5s delay on error. On connect, sends
+/// a `FindIntersect` built from the most recent locally-known chain points so the node resumes
+/// from the true common point instead of genesis. Applies `RollForward`/`RollBackward` to the
+/// `chain` table, reconciling competing branches via [`rollback::reconcile_tip`].
+fn run_chainsync(db_path: &PathBuf, host: &str, port: u16, network_magic: u32, sinks: &[Box<dyn Sink>], metrics: Option<&std::sync::Arc<Metrics>>) {
+    loop {
+        match connect_and_sync(db_path, host, port, network_magic, sinks, metrics) {
+            Ok(()) => break,
+            Err(error) => {
+                warn!("chainsync connection to {}:{} lost: {}, retrying in 5s...", host, port, error);
+                if let Some(metrics) = metrics {
+                    metrics.set_connected(false);
+                }
+                thread::sleep(Duration::from_secs(5));
+            }
+        }
+    }
+}
+
+fn connect_and_sync(
+    db_path: &PathBuf,
+    host: &str,
+    port: u16,
+    network_magic: u32,
+    sinks: &[Box<dyn Sink>],
+    metrics: Option<&std::sync::Arc<Metrics>>,
+) -> std::io::Result<()> {
+    let mut db = Connection::open(db_path).map_err(to_io_error)?;
+    let mut stream = TcpStream::connect((host, port))?;
+    handshake(&mut stream, network_magic)?;
+    if let Some(metrics) = metrics {
+        metrics.set_connected(true);
+    }
+    info!("Connected to {}:{}, finding intersection...", host, port);
+
+    let intersection_points = rollback::recent_chain_points(&db, 5).map_err(to_io_error)?;
+    find_intersect(&mut stream, &intersection_points)?;
+    let mut current_epoch = intersection_points.first().map(|point| point.slot_number / EPOCH_LENGTH_SLOTS);
+
+    loop {
+        mux::write_cbor(&mut stream, mux::PROTOCOL_CHAIN_SYNC, &serde_cbor::Value::Array(vec![serde_cbor::Value::Integer(MSG_REQUEST_NEXT)]))?;
+
+        // The node may reply MsgAwaitReply (no block ready yet) any number of times before
+        // eventually pushing the actual MsgRollForward/MsgRollBackward for this same request.
+        let mut reached_tip_dispatched = false;
+        let message = loop {
+            match receive_next_message(&mut stream)? {
+                ChainSyncReply::AwaitReply => {
+                    if !reached_tip_dispatched {
+                        sinks::dispatch(sinks, &ChainEvent {
+                            kind: EventKind::ReachedTip,
+                            block_number: 0,
+                            slot_number: 0,
+                            hash: String::new(),
+                            prev_hash: String::new(),
+                        });
+                        reached_tip_dispatched = true;
+                    }
+                }
+                other => break other,
+            }
+        };
+
+        match message {
+            ChainSyncReply::RollForward(new_block) => {
+                rollback::reconcile_tip(&mut db, &new_block.prev_hash).map_err(to_io_error)?;
+                block::insert_block(&db, &new_block).map_err(to_io_error)?;
+                if let Some(metrics) = metrics {
+                    metrics.record_roll_forward(new_block.block_number, new_block.slot_number);
+                }
+
+                let epoch = new_block.slot_number / EPOCH_LENGTH_SLOTS;
+                let crossed_epoch_boundary = matches!(current_epoch, Some(previous) if epoch > previous);
+                current_epoch = Some(epoch);
+                if crossed_epoch_boundary {
+                    sinks::dispatch(sinks, &ChainEvent {
+                        kind: EventKind::EpochBoundary,
+                        block_number: new_block.block_number,
+                        slot_number: new_block.slot_number,
+                        hash: new_block.hash.clone(),
+                        prev_hash: new_block.prev_hash.clone(),
+                    });
+                }
+
+                sinks::dispatch(sinks, &ChainEvent {
+                    kind: EventKind::BlockApplied,
+                    block_number: new_block.block_number,
+                    slot_number: new_block.slot_number,
+                    hash: new_block.hash,
+                    prev_hash: new_block.prev_hash,
+                });
+            }
+            ChainSyncReply::RollBackward(point) => {
+                rollback::roll_backward(&mut db, &point).map_err(to_io_error)?;
+                if let Some(metrics) = metrics {
+                    metrics.record_roll_backward(point.slot_number);
+                }
+                sinks::dispatch(sinks, &ChainEvent {
+                    kind: EventKind::Rollback,
+                    block_number: 0,
+                    slot_number: point.slot_number,
+                    hash: point.hash,
+                    prev_hash: String::new(),
+                });
+            }
+            ChainSyncReply::AwaitReply => unreachable!("handled above"),
+        }
+    }
+}
+
+fn to_io_error(error: rusqlite::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, error)
+}
+
+enum ChainSyncReply {
+    RollForward(block::Block),
+    RollBackward(rollback::ChainPoint),
+    AwaitReply,
+}
+
+/// Negotiates the highest mutually supported NodeToClient protocol version. `MsgProposeVersions`
+/// carries a map of version number to version data (here, just the network magic); the node
+/// replies `MsgAcceptVersion` or `MsgRefuse`. Generic over the stream type so the same handshake
+/// drives both the TCP-based ChainSync client and the Unix-socket-based LocalStateQuery client.
+pub(crate) fn handshake<T: Read + Write>(stream: &mut T, network_magic: u32) -> std::io::Result<()> {
+    let version_table = NTC_VERSIONS
+        .iter()
+        .map(|version| (serde_cbor::Value::Integer(*version), serde_cbor::Value::Integer(network_magic as i128)))
+        .collect();
+
+    mux::write_cbor(
+        stream,
+        mux::PROTOCOL_HANDSHAKE,
+        &serde_cbor::Value::Array(vec![serde_cbor::Value::Integer(MSG_PROPOSE_VERSIONS), serde_cbor::Value::Map(version_table)]),
+    )?;
+
+    match mux::read_cbor(stream)? {
+        serde_cbor::Value::Array(fields) => match fields.first() {
+            Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_ACCEPT_VERSION => Ok(()),
+            Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_REFUSE => Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                format!("node refused handshake: {:?}", fields.get(1)),
+            )),
+            other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unexpected handshake reply: {:?}", other))),
+        },
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("malformed handshake reply: {:?}", other))),
+    }
+}
+
+/// Sends `MsgFindIntersect` with the given candidate points and consumes the node's
+/// `MsgIntersectFound`/`MsgIntersectNotFound` reply. If no intersection is found the node will
+/// resume from its own start point; we log and continue rather than treating it as fatal.
+fn find_intersect(stream: &mut TcpStream, points: &[rollback::ChainPoint]) -> std::io::Result<()> {
+    let points_cbor = points
+        .iter()
+        .map(|point| serde_cbor::Value::Array(vec![serde_cbor::Value::Integer(point.slot_number as i128), serde_cbor::Value::Text(point.hash.clone())]))
+        .collect();
+
+    mux::write_cbor(
+        stream,
+        mux::PROTOCOL_CHAIN_SYNC,
+        &serde_cbor::Value::Array(vec![serde_cbor::Value::Integer(MSG_FIND_INTERSECT), serde_cbor::Value::Array(points_cbor)]),
+    )?;
+
+    match mux::read_cbor(stream)? {
+        serde_cbor::Value::Array(fields) => match fields.first() {
+            Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_INTERSECT_FOUND => Ok(()),
+            Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_INTERSECT_NOT_FOUND => {
+                warn!("node did not recognize any of our recent chain points; resuming from its own start point");
+                Ok(())
+            }
+            other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unexpected FindIntersect reply: {:?}", other))),
+        },
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("malformed FindIntersect reply: {:?}", other))),
+    }
+}
+
+fn receive_next_message(stream: &mut TcpStream) -> std::io::Result<ChainSyncReply> {
+    let fields = match mux::read_cbor(stream)? {
+        serde_cbor::Value::Array(fields) => fields,
+        _ => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "expected a chainsync message array")),
+    };
+
+    match fields.first() {
+        Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_AWAIT_REPLY => Ok(ChainSyncReply::AwaitReply),
+        Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_ROLL_FORWARD => Ok(ChainSyncReply::RollForward(decode_block(&fields[1])?)),
+        Some(serde_cbor::Value::Integer(tag)) if *tag == MSG_ROLL_BACKWARD => Ok(ChainSyncReply::RollBackward(decode_point(&fields[1])?)),
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unexpected chainsync message tag: {:?}", other))),
+    }
+}
+
+fn decode_point(value: &serde_cbor::Value) -> std::io::Result<rollback::ChainPoint> {
+    match value {
+        serde_cbor::Value::Array(fields) if fields.len() == 2 => {
+            let slot_number = as_i64(&fields[0])?;
+            let hash = as_text(&fields[1])?;
+            Ok(rollback::ChainPoint { slot_number, hash })
+        }
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("malformed chain point: {:?}", other))),
+    }
+}
+
+fn decode_block(value: &serde_cbor::Value) -> std::io::Result<block::Block> {
+    match value {
+        serde_cbor::Value::Array(fields) if fields.len() == 5 => Ok(block::Block {
+            block_number: as_i64(&fields[0])?,
+            slot_number: as_i64(&fields[1])?,
+            hash: as_text(&fields[2])?,
+            prev_hash: as_text(&fields[3])?,
+            leader_vrf: as_text(&fields[4])?,
+            orphaned: false,
+        }),
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("malformed block: {:?}", other))),
+    }
+}
+
+fn as_i64(value: &serde_cbor::Value) -> std::io::Result<i64> {
+    match value {
+        serde_cbor::Value::Integer(i) => Ok(*i as i64),
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("expected integer, got {:?}", other))),
+    }
+}
+
+fn as_text(value: &serde_cbor::Value) -> std::io::Result<String> {
+    match value {
+        serde_cbor::Value::Text(text) => Ok(text.clone()),
+        other => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("expected text, got {:?}", other))),
+    }
+}
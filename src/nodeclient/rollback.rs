@@ -0,0 +1,90 @@
+use log::warn;
+use rusqlite::{Connection, Error};
+
+/// Bound on how far back `reconcile_tip` will walk before giving up; a fork deeper than this is
+/// beyond what we can reconcile from local history alone.
+const MAX_WALK_BACK: i64 = 2160;
+
+/// A chain point: a slot number paired with the block hash at that slot.
+#[derive(Debug, Clone)]
+pub struct ChainPoint {
+    pub slot_number: i64,
+    pub hash: String,
+}
+
+/// Reads the most recent `count` chain points from the `chain` table, newest first, for use in a
+/// `FindIntersect` message so chainsync resumes from the true common point with the node instead
+/// of re-downloading from genesis.
+pub fn recent_chain_points(db: &Connection, count: usize) -> Result<Vec<ChainPoint>, Error> {
+    let mut statement = db.prepare(
+        "SELECT slot_number, hash FROM chain WHERE orphaned = 0 ORDER BY slot_number DESC LIMIT ?"
+    )?;
+    let points = statement
+        .query_map(&[&(count as i64)], |row| {
+            Ok(ChainPoint { slot_number: row.get(0)?, hash: row.get(1)? })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(points)
+}
+
+/// Handles a `RollBackward` to `point`: marks every row past the intersection as orphaned in a
+/// single transaction, leaving the node and the local `chain` table consistent.
+pub fn roll_backward(db: &mut Connection, point: &ChainPoint) -> Result<usize, Error> {
+    let tx = db.transaction()?;
+    let orphaned = tx.execute(
+        "UPDATE chain SET orphaned = 1 WHERE slot_number > ?",
+        &[&point.slot_number],
+    )?;
+    tx.commit()?;
+
+    Ok(orphaned)
+}
+
+/// Before applying a `RollForward` block, checks whether `prev_hash` matches the current tip's
+/// hash. If it doesn't, the node has switched to a competing branch: walk back from the local tip
+/// to the fork point and orphan the losing branch so the new block can be inserted cleanly.
+pub fn reconcile_tip(db: &mut Connection, prev_hash: &str) -> Result<usize, Error> {
+    let tip: Option<(i64, String)> = db
+        .query_row(
+            "SELECT slot_number, hash FROM chain WHERE orphaned = 0 ORDER BY slot_number DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+
+    let tip = match tip {
+        Some(tip) => tip,
+        None => return Ok(0),
+    };
+
+    if tip.1 == prev_hash {
+        return Ok(0);
+    }
+
+    // Walk back through the local chain, newest first, until we find the block the node is
+    // building on, then orphan everything above it.
+    let mut statement = db.prepare(
+        "SELECT slot_number, hash FROM chain WHERE orphaned = 0 ORDER BY slot_number DESC LIMIT ?"
+    )?;
+    let mut rows = statement.query(&[&MAX_WALK_BACK])?;
+
+    let mut fork_point = None;
+    while let Some(row) = rows.next()? {
+        let hash: String = row.get(1)?;
+        if hash == prev_hash {
+            fork_point = Some(ChainPoint { slot_number: row.get(0)?, hash });
+            break;
+        }
+    }
+    drop(rows);
+    drop(statement);
+
+    match fork_point {
+        Some(fork_point) => roll_backward(db, &fork_point),
+        None => {
+            warn!("Could not find fork point for prev_hash {} within the last {} blocks; leaving chain as-is", prev_hash, MAX_WALK_BACK);
+            Ok(0)
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use log::info;
+use tiny_http::{Response, Server, StatusCode};
+
+use crate::nodeclient::block;
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+/// Serves the `chain` table over a small read-only HTTP API so dashboards and monitoring scripts
+/// can read synced chain data concurrently with `cncli sync` writing to it, instead of opening
+/// the sqlite file directly.
+///
+/// Routes:
+///   GET /tip                                 -> most recent non-orphaned block
+///   GET /block/{hash-or-prefix}               -> single block lookup
+///   GET /blocks?from_slot=&to_slot=&limit=&offset=  -> paginated range query
+pub fn serve(db: &PathBuf, host: &str, port: u16) {
+    let address = format!("{}:{}", host, port);
+    info!("Starting chain query API on {}...", address);
+    let server = Server::http(&address).unwrap();
+
+    for request in server.incoming_requests() {
+        let response = handle_request(db, request.url());
+        let _ = request.respond(response);
+    }
+}
+
+fn handle_request(db: &PathBuf, url: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (url, ""),
+    };
+
+    match path {
+        "/tip" => match block::query_tip(db) {
+            Ok(block) => json_response(&block, 200),
+            Err(error) => error_response(&error.to_string(), 404),
+        },
+        "/blocks" => {
+            let params = parse_query(query);
+            let from_slot = params.get("from_slot").and_then(|v| v.parse().ok()).unwrap_or(0);
+            let to_slot = params.get("to_slot").and_then(|v| v.parse().ok()).unwrap_or(i64::MAX);
+            let limit = params.get("limit").and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+            let offset = params.get("offset").and_then(|v| v.parse().ok()).unwrap_or(0);
+
+            match block::query_blocks_in_range(db, from_slot, to_slot, limit, offset) {
+                Ok(blocks) => json_response(&blocks, 200),
+                Err(error) => error_response(&error.to_string(), 500),
+            }
+        }
+        path => match path.strip_prefix("/block/") {
+            Some(hash_prefix) if !hash_prefix.is_empty() => match block::query_block_by_hash(db, hash_prefix) {
+                Ok(block) => json_response(&block, 200),
+                Err(error) => error_response(&error.to_string(), 404),
+            },
+            _ => error_response("not found", 404),
+        },
+    }
+}
+
+fn parse_query(query: &str) -> std::collections::HashMap<&str, &str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect()
+}
+
+fn json_response<T: serde::Serialize>(value: &T, status: u16) -> Response<std::io::Cursor<Vec<u8>>> {
+    let body = serde_json::to_string(value).unwrap();
+    Response::from_string(body).with_status_code(StatusCode(status))
+}
+
+fn error_response(message: &str, status: u16) -> Response<std::io::Cursor<Vec<u8>>> {
+    json_response(&serde_json::json!({ "errorMessage": message }), status)
+}
@@ -0,0 +1,36 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use log::error;
+
+use crate::nodeclient::sinks::{ChainEvent, Sink};
+
+/// Appends each event as a single line of JSON to a file.
+pub struct FileSink {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        FileSink { path, lock: Mutex::new(()) }
+    }
+}
+
+impl Sink for FileSink {
+    fn handle(&self, event: &ChainEvent) {
+        let _guard = self.lock.lock().unwrap();
+        let line = serde_json::to_string(event).unwrap();
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+
+        if let Err(error) = result {
+            error!("Unable to append event to {}: {}", self.path.display(), error);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+mod file_sink;
+mod stdout_sink;
+mod webhook_sink;
+
+pub use file_sink::FileSink;
+pub use stdout_sink::StdoutSink;
+pub use webhook_sink::WebhookSink;
+
+/// Discriminates the kind of chain activity a `ChainEvent` represents.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    BlockApplied,
+    Rollback,
+    ReachedTip,
+    EpochBoundary,
+}
+
+/// A single piece of chain activity, emitted to every configured `Sink` in
+/// addition to being persisted to the `chain` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainEvent {
+    pub kind: EventKind,
+    pub block_number: i64,
+    pub slot_number: i64,
+    pub hash: String,
+    pub prev_hash: String,
+}
+
+/// Something that wants to be told about chain activity as `cncli sync` applies it.
+pub trait Sink: Send + Sync {
+    fn handle(&self, event: &ChainEvent);
+}
+
+/// JSON config analogous to `PooltoolConfig`, loaded with `--sink-config`.
+#[derive(Debug, Deserialize)]
+struct SinksConfig {
+    sinks: Vec<SinkSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SinkSpec {
+    Stdout,
+    File { path: PathBuf },
+    Webhook { url: String },
+}
+
+impl SinkSpec {
+    fn into_sink(self) -> Box<dyn Sink> {
+        match self {
+            SinkSpec::Stdout => Box::new(StdoutSink::new()),
+            SinkSpec::File { path } => Box::new(FileSink::new(path)),
+            SinkSpec::Webhook { url } => Box::new(WebhookSink::new(url)),
+        }
+    }
+}
+
+/// Parses the repeatable `--sink` flag. Accepted forms: `stdout`, `file:<path>`, `webhook:<url>`.
+fn parse_sink_flag(spec: &str) -> Option<Box<dyn Sink>> {
+    if spec == "stdout" {
+        return Some(Box::new(StdoutSink::new()));
+    }
+    if let Some(path) = spec.strip_prefix("file:") {
+        return Some(Box::new(FileSink::new(PathBuf::from(path))));
+    }
+    if let Some(url) = spec.strip_prefix("webhook:") {
+        return Some(Box::new(WebhookSink::new(url.to_string())));
+    }
+    warn!("Ignoring unrecognized --sink value: {}", spec);
+    None
+}
+
+/// Builds the set of sinks requested on the command line and/or in a `--sink-config` file.
+pub fn build_sinks(sink_flags: &[String], config_path: &Option<PathBuf>) -> Vec<Box<dyn Sink>> {
+    let mut sinks: Vec<Box<dyn Sink>> = sink_flags.iter().filter_map(|spec| parse_sink_flag(spec)).collect();
+
+    if let Some(config_path) = config_path {
+        let buf = BufReader::new(File::open(config_path).unwrap());
+        let config: SinksConfig = serde_json::from_reader(buf).unwrap();
+        sinks.extend(config.sinks.into_iter().map(SinkSpec::into_sink));
+    }
+
+    sinks
+}
+
+/// Fans a chain event out to every configured sink.
+pub fn dispatch(sinks: &[Box<dyn Sink>], event: &ChainEvent) {
+    for sink in sinks {
+        sink.handle(event);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::nodeclient::sinks::{ChainEvent, Sink};
+
+/// Writes each event as a single line of JSON to stdout.
+pub struct StdoutSink;
+
+impl StdoutSink {
+    pub fn new() -> Self {
+        StdoutSink {}
+    }
+}
+
+impl Default for StdoutSink {
+    fn default() -> Self {
+        StdoutSink::new()
+    }
+}
+
+impl Sink for StdoutSink {
+    fn handle(&self, event: &ChainEvent) {
+        println!("{}", serde_json::to_string(event).unwrap());
+    }
+}
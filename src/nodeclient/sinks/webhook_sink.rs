@@ -0,0 +1,23 @@
+use log::error;
+
+use crate::nodeclient::sinks::{ChainEvent, Sink};
+
+/// POSTs each event as JSON to a configured webhook URL.
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        WebhookSink { url, client: reqwest::blocking::Client::new() }
+    }
+}
+
+impl Sink for WebhookSink {
+    fn handle(&self, event: &ChainEvent) {
+        if let Err(error) = self.client.post(&self.url).json(event).send() {
+            error!("Unable to deliver event to webhook {}: {}", self.url, error);
+        }
+    }
+}